@@ -1,4 +1,10 @@
-use std::{collections::HashMap, fmt, hash::Hash};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    hash::Hash,
+    io::Read,
+};
 
 use bit_vec::BitVec;
 
@@ -8,6 +14,12 @@ pub enum Error {
     NoSuchKey(String),
     #[error("Invalid weight nodes: {0}")]
     InvalidWeights(String),
+    #[error("Invalid encoded data: {0}")]
+    InvalidEncoding(String),
+    #[error("Need more data to decode the next symbol")]
+    NeedMoreData,
+    #[error("I/O error while decoding: {0}")]
+    Io(String),
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -30,6 +42,146 @@ impl<T> Node<T> {
     }
 }
 
+/// A node queued for merging, ordered by `(freq, seq)` so that the binary
+/// heap produces a deterministic merge order: ties in frequency are broken
+/// by insertion order rather than however the heap happens to store them.
+struct QueueEntry<T> {
+    freq: u32,
+    seq: u64,
+    node: Box<Node<T>>,
+}
+
+impl<T> PartialEq for QueueEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.freq == other.freq && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for QueueEntry<T> {}
+
+impl<T> PartialOrd for QueueEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for QueueEntry<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.freq.cmp(&other.freq).then_with(|| self.seq.cmp(&other.seq))
+    }
+}
+
+/// Assigns canonical codes to `symbols`, which must already be sorted by `(code_length,
+/// symbol_order)`. Canonical codes are the lexicographically smallest codes consistent with the
+/// given lengths: starting from 0, each symbol gets the current code (left-padded to its
+/// length), the code is incremented, and it is left-shifted whenever the next symbol's length is
+/// longer.
+fn assign_canonical_codes<T>(symbols: Vec<(T, u8)>) -> Vec<(T, BitVec)> {
+    let mut code: u32 = 0;
+    let mut prev_len = symbols.first().map_or(0, |(_, len)| *len);
+    symbols
+        .into_iter()
+        .map(|(value, len)| {
+            code <<= (len - prev_len) as u32;
+            prev_len = len;
+            let mut bits = BitVec::from_elem(len as usize, false);
+            for i in 0..len {
+                bits.set((len - 1 - i) as usize, (code >> i) & 1 == 1);
+            }
+            code += 1;
+            (value, bits)
+        })
+        .collect()
+}
+
+/// Rebuilds a tree from a set of (value, code) pairs, e.g. ones produced by
+/// [`assign_canonical_codes`]. The tree's shape carries no frequency information, so internal
+/// nodes are created with a `freq` of 0.
+fn tree_from_codes<T>(codes: Vec<(T, BitVec)>) -> Node<T> {
+    let mut root = Node::new(0, None);
+    for (value, bits) in codes {
+        if bits.is_empty() {
+            root.value = Some(value);
+            continue;
+        }
+        let mut current = &mut root;
+        let last = bits.len() - 1;
+        for (i, bit) in bits.iter().enumerate() {
+            let branch = if bit {
+                &mut current.right
+            } else {
+                &mut current.left
+            };
+            if i == last {
+                *branch = Some(Box::new(Node::new(0, Some(value))));
+                break;
+            }
+            current = &mut *branch.get_or_insert_with(|| Box::new(Node::new(0, None)));
+        }
+    }
+    root
+}
+
+/// A "coin" in the package-merge algorithm: an item carrying the original symbol indices that
+/// contributed to its weight, so that participation counts can be recovered at the end.
+#[derive(Clone)]
+struct Coin {
+    weight: u64,
+    symbols: Vec<usize>,
+}
+
+/// Computes code lengths, no longer than `max_bits`, for symbols weighted by `weights` (indexed
+/// positionally), using the package-merge algorithm. There are `max_bits` levels; level 1 is
+/// just the sorted original symbols, and each subsequent level packages adjacent coins from the
+/// previous level and merges those packages back in with the original symbols. The symbol
+/// indices a coin carries are the ones whose code length it contributes one bit to. Taking the
+/// cheapest `2 * n - 2` coins from the final level and counting each symbol's participation
+/// yields lengths that satisfy Kraft's inequality.
+fn package_merge_lengths(weights: &[u64], max_bits: u32) -> Vec<u8> {
+    let n = weights.len();
+    if n == 1 {
+        // A single-symbol alphabet still needs a 1-bit code: the formula below (built around
+        // selecting `2 * n - 2` coins) degenerates to 0 at n == 1, which would silently produce
+        // a zero-length code and lose every symbol on encode.
+        return vec![1];
+    }
+    let mut originals: Vec<Coin> = weights
+        .iter()
+        .enumerate()
+        .map(|(symbol, &weight)| Coin {
+            weight,
+            symbols: vec![symbol],
+        })
+        .collect();
+    originals.sort_by_key(|coin| coin.weight);
+
+    let mut current = originals.clone();
+    for _ in 0..max_bits.saturating_sub(1) {
+        let mut merged = current
+            .chunks_exact(2)
+            .map(|pair| {
+                let mut symbols = pair[0].symbols.clone();
+                symbols.extend_from_slice(&pair[1].symbols);
+                Coin {
+                    weight: pair[0].weight + pair[1].weight,
+                    symbols,
+                }
+            })
+            .collect::<Vec<_>>();
+        merged.extend(originals.iter().cloned());
+        merged.sort_by_key(|coin| coin.weight);
+        current = merged;
+    }
+
+    let mut lengths = vec![0u8; n];
+    for coin in current.into_iter().take(2 * n - 2) {
+        for symbol in coin.symbols {
+            lengths[symbol] += 1;
+        }
+    }
+    lengths
+}
+
 pub struct Encoder<T> {
     encoding: HashMap<T, BitVec>,
 }
@@ -58,11 +210,37 @@ where
             }
         }
         let mut encoding = HashMap::new();
-        let bits = BitVec::new();
-        assign(&root, &mut encoding, bits);
+        if let Some(value) = root.value.as_ref() {
+            // A single-symbol alphabet: the root is itself the only leaf, with no path of
+            // branches to encode its length. Give it a 1-bit code instead of an empty one, or
+            // `encode`/`decode` would round-trip any amount of data as zero bits.
+            let mut bits = BitVec::new();
+            bits.push(false);
+            encoding.insert(value.clone(), bits);
+        } else {
+            assign(root, &mut encoding, BitVec::new());
+        }
         Self { encoding }
     }
 
+    /// Returns the bit-length of each symbol's code, ordered by `(length, symbol)`. That order
+    /// makes the returned list identical across independent builds of the same weights:
+    /// iterating `self.encoding` (a `HashMap`) would leave equal-length symbols in an
+    /// arbitrary, run-to-run-unstable order. Requiring `T: Ord` lets us break ties on the
+    /// symbol itself instead.
+    pub fn code_lengths(&self) -> Vec<(T, u8)>
+    where
+        T: Ord,
+    {
+        let mut lengths = self
+            .encoding
+            .iter()
+            .map(|(value, bits)| (value.clone(), bits.len() as u8))
+            .collect::<Vec<_>>();
+        lengths.sort_by(|(a, a_len), (b, b_len)| a_len.cmp(b_len).then_with(|| a.cmp(b)));
+        lengths
+    }
+
     pub fn encode(&self, data: &[T]) -> Result<BitVec> {
         let mut vec = BitVec::new();
         for item in data {
@@ -75,6 +253,19 @@ where
         }
         Ok(vec)
     }
+
+    /// Encodes data into a packed byte stream instead of an in-memory `BitVec`. Bits are packed
+    /// MSB-first, and the final partial byte (if any) is padded out with `1` bits, following the
+    /// convention used by QPACK/HPACK Huffman coding. Pair with
+    /// [`Decoder::decode_from_bytes`], which needs the original symbol count to know where the
+    /// data ends and the padding begins.
+    pub fn encode_to_bytes(&self, data: &[T]) -> Result<Vec<u8>> {
+        let mut bits = self.encode(data)?;
+        while bits.len() % 8 != 0 {
+            bits.push(true);
+        }
+        Ok(bits.to_bytes())
+    }
 }
 
 pub struct Decoder<T> {
@@ -104,6 +295,142 @@ impl<T> Decoder<T> {
     {
         self.decode_iter(encoded).cloned().collect()
     }
+
+    /// Decodes a packed byte stream produced by [`Encoder::encode_to_bytes`]. Since the padding
+    /// bits that fill out the final byte are indistinguishable from real code bits by shape
+    /// alone, the caller must supply `symbol_count`, the number of symbols that were originally
+    /// encoded. Decoding stops as soon as `symbol_count` symbols have been read, and the
+    /// remaining trailing bits are checked to be fewer than 8 and all `1`s, per the padding
+    /// convention in [`Encoder::encode_to_bytes`]. Returns [`Error::InvalidEncoding`] if there
+    /// isn't enough data to decode `symbol_count` symbols, or if the padding doesn't check out.
+    pub fn decode_from_bytes(&self, bytes: &[u8], symbol_count: usize) -> Result<Vec<T>>
+    where
+        T: Clone,
+    {
+        let bits = BitVec::from_bytes(bytes);
+        let mut input = bits.iter();
+        let mut current_node = &self.root;
+        let mut decoded = Vec::with_capacity(symbol_count);
+
+        while decoded.len() < symbol_count {
+            let bit = input.next().ok_or_else(|| {
+                Error::InvalidEncoding("Not enough data to decode symbol_count symbols".to_string())
+            })?;
+            if bit {
+                if let Some(ref right) = current_node.right {
+                    current_node = right;
+                }
+            } else if let Some(ref left) = current_node.left {
+                current_node = left;
+            }
+            if let Some(value) = current_node.value.as_ref() {
+                decoded.push(value.clone());
+                current_node = &self.root;
+            }
+        }
+
+        let padding: Vec<bool> = input.collect();
+        if padding.len() >= 8 || padding.iter().any(|bit| !bit) {
+            return Err(Error::InvalidEncoding(
+                "Trailing padding must be fewer than 8 all-one bits".to_string(),
+            ));
+        }
+
+        Ok(decoded)
+    }
+
+    /// Wraps a byte [`Read`] source in a [`StreamDecoder`] that decodes one symbol at a time,
+    /// without requiring the whole encoded message to be in memory up front. Like
+    /// [`Decoder::decode_from_bytes`], the caller must supply `symbol_count` (the padding bits
+    /// `Encoder::encode_to_bytes` fills the final byte with are indistinguishable from real code
+    /// bits by shape alone). Once `symbol_count` symbols have been decoded,
+    /// [`StreamDecoder::next_symbol`] checks the padding and returns `Ok(None)`.
+    pub fn stream<R: Read>(&self, reader: R, symbol_count: usize) -> StreamDecoder<'_, T, R> {
+        StreamDecoder {
+            root: &self.root,
+            current_node: &self.root,
+            reader,
+            byte: 0,
+            bit_index: 8,
+            decoded: 0,
+            symbol_count,
+        }
+    }
+}
+
+/// An incremental decoder that pulls bytes from `R` on demand instead of requiring the whole
+/// encoded message up front. Created with [`Decoder::stream`].
+pub struct StreamDecoder<'a, T, R> {
+    root: &'a Node<T>,
+    current_node: &'a Node<T>,
+    reader: R,
+    byte: u8,
+    bit_index: u8,
+    decoded: usize,
+    symbol_count: usize,
+}
+
+impl<'a, T, R: Read> StreamDecoder<'a, T, R> {
+    /// Decodes and returns the next symbol, reading more bytes from the underlying reader as
+    /// needed. Once `symbol_count` symbols have been decoded, checks that the remaining bits in
+    /// the current byte are fewer than 8 and all `1`s, per the padding convention in
+    /// [`Encoder::encode_to_bytes`], and returns `Ok(None)`; returns [`Error::InvalidEncoding`]
+    /// if the padding doesn't check out or the reader has more data than expected.
+    /// Returns `Err(Error::NeedMoreData)` if the reader is exhausted before `symbol_count`
+    /// symbols have been decoded — callers fed a stream in chunks can treat that as "come back
+    /// once more bytes are available".
+    pub fn next_symbol(&mut self) -> Result<Option<&'a T>> {
+        if self.decoded == self.symbol_count {
+            while self.bit_index < 8 {
+                let bit = (self.byte >> (7 - self.bit_index)) & 1 == 1;
+                self.bit_index += 1;
+                if !bit {
+                    return Err(Error::InvalidEncoding(
+                        "Trailing padding must be all-one bits".to_string(),
+                    ));
+                }
+            }
+            let mut buf = [0u8; 1];
+            return match self.reader.read(&mut buf) {
+                Ok(0) => Ok(None),
+                Ok(_) => Err(Error::InvalidEncoding(
+                    "Unexpected data after symbol_count symbols".to_string(),
+                )),
+                Err(err) => Err(Error::Io(err.to_string())),
+            };
+        }
+
+        loop {
+            if self.bit_index == 8 {
+                let mut buf = [0u8; 1];
+                match self.reader.read(&mut buf) {
+                    Ok(0) => return Err(Error::NeedMoreData),
+                    Ok(_) => {
+                        self.byte = buf[0];
+                        self.bit_index = 0;
+                    }
+                    Err(err) => return Err(Error::Io(err.to_string())),
+                }
+            }
+
+            let bit = (self.byte >> (7 - self.bit_index)) & 1 == 1;
+            self.bit_index += 1;
+
+            if bit {
+                if let Some(ref right) = self.current_node.right {
+                    self.current_node = right;
+                }
+            } else if let Some(ref left) = self.current_node.left {
+                self.current_node = left;
+            }
+
+            if let Some(value) = self.current_node.value.as_ref() {
+                self.current_node = self.root;
+                self.decoded += 1;
+                return Ok(Some(value));
+            }
+        }
+    }
 }
 
 struct DecoderIter<'a, T> {
@@ -124,7 +451,7 @@ impl<'a, T> Iterator for DecoderIter<'a, T> {
             self.current_node = left;
         }
         if let Some(value) = self.current_node.value.as_ref() {
-            self.current_node = &self.root;
+            self.current_node = self.root;
             Some(value)
         } else {
             self.next()
@@ -142,38 +469,161 @@ where
     T: Eq + Hash + Clone + fmt::Debug,
 {
     pub fn new(weights: impl IntoIterator<Item = (T, u32)>) -> Result<Self> {
-        let mut nodes = weights
+        let mut seq: u64 = 0;
+        let mut heap = weights
             .into_iter()
-            .map(|(value, frequency)| Box::new(Node::new(frequency, Some(value))))
-            .collect::<Vec<_>>();
+            .map(|(value, frequency)| {
+                let entry = QueueEntry {
+                    freq: frequency,
+                    seq,
+                    node: Box::new(Node::new(frequency, Some(value))),
+                };
+                seq += 1;
+                Reverse(entry)
+            })
+            .collect::<BinaryHeap<_>>();
 
-        while nodes.len() > 1 {
-            nodes.sort_by(|a, b| (&(b.freq)).cmp(&(a.freq)));
-            let a = nodes
+        while heap.len() > 1 {
+            let Reverse(a) = heap
                 .pop()
                 .ok_or_else(|| Error::InvalidWeights("Expected at least 1 node".to_string()))?;
-            let b = nodes
+            let Reverse(b) = heap
                 .pop()
                 .ok_or_else(|| Error::InvalidWeights("Expected at least 1 node".to_string()))?;
             let mut c = Node::new(a.freq + b.freq, None);
-            c.left = Some(a);
-            c.right = Some(b);
-            nodes.push(Box::new(c));
+            c.left = Some(a.node);
+            c.right = Some(b.node);
+            heap.push(Reverse(QueueEntry {
+                freq: c.freq,
+                seq,
+                node: Box::new(c),
+            }));
+            seq += 1;
         }
 
-        let root = *nodes
+        let Reverse(root_entry) = heap
             .pop()
             .ok_or_else(|| Error::InvalidWeights("Expected root node".to_string()))?;
+        let root = *root_entry.node;
         let encoder = Encoder::new(&root);
         let decoder = Decoder::new(root);
         Ok(Self { encoder, decoder })
     }
 
+    /// Builds a codebook from the frequencies of the values produced by `data`, i.e. counts
+    /// how often each value occurs and feeds those counts into [`Huffman::new`].
+    pub fn from_frequencies(data: impl IntoIterator<Item = T>) -> Result<Self> {
+        let mut counts = HashMap::new();
+        for value in data {
+            *counts.entry(value).or_insert(0u32) += 1;
+        }
+        Self::new(counts)
+    }
+
+    /// Builds a codebook from a sample of data, counting the frequency of each value. This is
+    /// the same as [`Huffman::from_frequencies`] but takes a slice instead of an owned iterator.
+    pub fn from_data(data: &[T]) -> Result<Self> {
+        Self::from_frequencies(data.iter().cloned())
+    }
+
+    /// Builds a codebook like [`Huffman::new`], but guarantees no code exceeds `max_bits`, using
+    /// the package-merge algorithm. Useful when a fixed-width decode table or a hardware/protocol
+    /// limit caps how long a code may be: the plain greedy tree can otherwise produce codes as
+    /// long as `n - 1` bits. Returns [`Error::InvalidWeights`] if `max_bits` is too small to fit
+    /// the alphabet, i.e. `2^max_bits < n`, or if `max_bits` is 0 (even a lone symbol gets a
+    /// 1-bit code).
+    pub fn with_max_length(
+        weights: impl IntoIterator<Item = (T, u32)>,
+        max_bits: u8,
+    ) -> Result<Self>
+    where
+        T: Ord,
+    {
+        let symbols: Vec<(T, u32)> = weights.into_iter().collect();
+        let n = symbols.len();
+        if n == 0 {
+            return Err(Error::InvalidWeights(
+                "Expected at least 1 node".to_string(),
+            ));
+        }
+        let capacity = 1u128.checked_shl(max_bits as u32).unwrap_or(u128::MAX);
+        if max_bits == 0 {
+            return Err(Error::InvalidWeights(
+                "max_bits must be at least 1".to_string(),
+            ));
+        }
+        if n as u128 > capacity {
+            return Err(Error::InvalidWeights(format!(
+                "max_bits {max_bits} cannot represent {n} symbols (2^{max_bits} < {n})"
+            )));
+        }
+
+        let freqs: Vec<u64> = symbols.iter().map(|(_, freq)| *freq as u64).collect();
+        let code_lengths = package_merge_lengths(&freqs, max_bits as u32);
+        let lengths = symbols
+            .into_iter()
+            .zip(code_lengths)
+            .map(|((value, _), len)| (value, len));
+        Self::from_code_lengths(lengths)
+    }
+
+    /// Rebuilds a codebook from a set of `(symbol, code_length)` pairs, e.g. ones previously
+    /// obtained from [`Huffman::code_lengths`]. The codes assigned are canonical: they depend
+    /// only on the lengths and the symbols' own ordering (`T: Ord`), not on the order in which
+    /// `lengths` lists them, so the same input always reproduces the same encoder and decoder. This is far cheaper to serialize than a
+    /// tree, at the cost of discarding the original frequencies.
+    pub fn from_code_lengths(lengths: impl IntoIterator<Item = (T, u8)>) -> Result<Self>
+    where
+        T: Ord,
+    {
+        let mut symbols: Vec<(T, u8)> = lengths.into_iter().collect();
+        if symbols.is_empty() {
+            return Err(Error::InvalidWeights(
+                "Expected at least 1 code length".to_string(),
+            ));
+        }
+        // Break ties on the symbol itself, not on whatever order the caller happened to supply
+        // them in, so that two independent builds of the same weights land on the same codes.
+        symbols.sort_by(|(a, a_len), (b, b_len)| a_len.cmp(b_len).then_with(|| a.cmp(b)));
+
+        let codes = assign_canonical_codes(symbols);
+        let encoding = codes.iter().cloned().collect();
+        let root = tree_from_codes(codes);
+        let encoder = Encoder { encoding };
+        let decoder = Decoder::new(root);
+        Ok(Self { encoder, decoder })
+    }
+
+    /// Returns the bit-length of each symbol's code. See [`Huffman::from_code_lengths`] for
+    /// rebuilding a codebook from these lengths.
+    pub fn code_lengths(&self) -> Vec<(T, u8)>
+    where
+        T: Ord,
+    {
+        self.encoder.code_lengths()
+    }
+
     /// Encodes data into a BitVec. Fails if any of the data is not present in the dictionary.
     pub fn encode(&self, data: &[T]) -> Result<BitVec> {
         self.encoder.encode(data)
     }
 
+    /// Encodes data into a packed byte stream. See [`Encoder::encode_to_bytes`].
+    pub fn encode_to_bytes(&self, data: &[T]) -> Result<Vec<u8>> {
+        self.encoder.encode_to_bytes(data)
+    }
+
+    /// Decodes a packed byte stream produced by [`Huffman::encode_to_bytes`]. See
+    /// [`Decoder::decode_from_bytes`].
+    pub fn decode_from_bytes(&self, bytes: &[u8], symbol_count: usize) -> Result<Vec<T>> {
+        self.decoder.decode_from_bytes(bytes, symbol_count)
+    }
+
+    /// Wraps a byte [`Read`] source in a [`StreamDecoder`]. See [`Decoder::stream`].
+    pub fn stream<R: Read>(&self, reader: R, symbol_count: usize) -> StreamDecoder<'_, T, R> {
+        self.decoder.stream(reader, symbol_count)
+    }
+
     pub fn decode<'a>(&'a self, encoded: &'a BitVec) -> Vec<&'a T> {
         self.decoder.decode(encoded)
     }
@@ -230,4 +680,205 @@ mod tests {
         let decoded = huffman.decode_owned(&encoded);
         assert_eq!(data, decoded);
     }
+
+    #[test]
+    fn test_from_data() {
+        let data = vec!['a', 'b', 'a', 'a', 'c', 'b', 'a'];
+        let huffman = Huffman::from_data(&data).unwrap();
+        let encoded = huffman.encode(&data).unwrap();
+        let decoded = huffman.decode_owned(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_from_data_single_symbol() {
+        // A degenerate one-symbol alphabet used to encode every value as zero bits, so decoding
+        // lost the whole sample. It must get a 1-bit code instead.
+        let data = vec!['a', 'a', 'a', 'a'];
+        let huffman = Huffman::from_data(&data).unwrap();
+        assert_eq!(huffman.code_lengths(), vec![('a', 1)]);
+
+        let encoded = huffman.encode(&data).unwrap();
+        assert_eq!(encoded.len(), data.len());
+        let decoded = huffman.decode_owned(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_from_frequencies() {
+        let data = vec!['a', 'b', 'a', 'a', 'c', 'b', 'a'];
+        let huffman = Huffman::from_frequencies(data.clone()).unwrap();
+        let encoded = huffman.encode(&data).unwrap();
+        let decoded = huffman.decode_owned(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_canonical_round_trip() {
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let lengths = huffman.code_lengths();
+        let rebuilt = Huffman::from_code_lengths(lengths).unwrap();
+
+        let data = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3];
+        let encoded = rebuilt.encode(&data).unwrap();
+        let decoded = rebuilt.decode_owned(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_canonical_code_lengths_are_stable() {
+        // Symbols 1 and 3 tie on frequency (and thus code length), which used to make
+        // `code_lengths()` order them however the underlying HashMap happened to, and
+        // `from_code_lengths` would then assign them different canonical codes from one build to
+        // the next. Rebuilding from the original weights twice must yield identical codes.
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let a = Huffman::new(weights.clone()).unwrap();
+        let b = Huffman::new(weights).unwrap();
+
+        assert_eq!(a.code_lengths(), b.code_lengths());
+
+        let data = vec![0, 1, 2, 3];
+        assert_eq!(
+            a.encode(&data).unwrap().to_bytes(),
+            b.encode(&data).unwrap().to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_bytes_round_trip() {
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let data = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3];
+
+        let bytes = huffman.encode_to_bytes(&data).unwrap();
+        let decoded = huffman.decode_from_bytes(&bytes, data.len()).unwrap();
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_decode_from_bytes_rejects_bad_padding() {
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let data = vec![0, 1];
+
+        let mut bytes = huffman.encode_to_bytes(&data).unwrap();
+        let last = bytes.last_mut().unwrap();
+        *last &= !1;
+        assert!(huffman.decode_from_bytes(&bytes, data.len()).is_err());
+    }
+
+    #[test]
+    fn test_decode_from_bytes_rejects_truncated_data() {
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let data = vec![0, 1, 2, 3];
+
+        let bytes = huffman.encode_to_bytes(&data).unwrap();
+        assert!(huffman.decode_from_bytes(&bytes, data.len() + 20).is_err());
+    }
+
+    #[test]
+    fn test_stream_decoder_round_trip() {
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let data = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3];
+
+        let bytes = huffman.encode_to_bytes(&data).unwrap();
+        let mut stream = huffman.stream(std::io::Cursor::new(&bytes), data.len());
+        let mut decoded = Vec::new();
+        for _ in 0..data.len() {
+            decoded.push(*stream.next_symbol().unwrap().unwrap());
+        }
+        assert_eq!(data, decoded);
+
+        // Past symbol_count, the trailing padding is validated rather than walked as data.
+        assert!(stream.next_symbol().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stream_decoder_needs_more_data_mid_symbol() {
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let lengths = huffman.code_lengths();
+        let longest = lengths.iter().map(|(_, len)| *len).max().unwrap();
+        let symbol = lengths
+            .iter()
+            .find(|(_, len)| *len == longest)
+            .map(|(value, _)| *value)
+            .unwrap();
+
+        // Three copies span more than a single byte (code length > 8 / 3 bits), so dropping the
+        // final byte is guaranteed to cut a symbol's code off partway through.
+        let data = vec![symbol; 3];
+        let mut bytes = huffman.encode_to_bytes(&data).unwrap();
+        bytes.pop();
+
+        let mut stream = huffman.stream(std::io::Cursor::new(&bytes), data.len());
+        let mut saw_need_more_data = false;
+        loop {
+            match stream.next_symbol() {
+                Ok(Some(_)) => continue,
+                Ok(None) => break,
+                Err(Error::NeedMoreData) => {
+                    saw_need_more_data = true;
+                    break;
+                }
+                Err(err) => panic!("unexpected error: {err}"),
+            }
+        }
+        assert!(saw_need_more_data);
+    }
+
+    #[test]
+    fn test_stream_decoder_does_not_decode_padding_bits() {
+        // Encoded output is byte-packed with trailing all-one padding bits (see
+        // `Encoder::encode_to_bytes`). Without a `symbol_count` to stop at, those padding bits
+        // look like more tree-walk bits and get decoded as spurious extra symbols.
+        let weights = vec![(0, 10), (1, 1), (2, 5), (3, 1)];
+        let huffman = Huffman::new(weights).unwrap();
+        let data = vec![0, 0, 0, 1, 2, 3];
+
+        let bytes = huffman.encode_to_bytes(&data).unwrap();
+        let mut stream = huffman.stream(std::io::Cursor::new(&bytes), data.len());
+        let mut decoded = Vec::new();
+        while let Some(value) = stream.next_symbol().unwrap() {
+            decoded.push(*value);
+        }
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_with_max_length_respects_limit() {
+        let weights = vec![(0, 1), (1, 1), (2, 2), (3, 3), (4, 5), (5, 8), (6, 13)];
+        let huffman = Huffman::with_max_length(weights, 3).unwrap();
+        let lengths = huffman.code_lengths();
+        assert!(lengths.iter().all(|(_, len)| *len <= 3));
+
+        let data = vec![0, 1, 2, 3, 4, 5, 6];
+        let encoded = huffman.encode(&data).unwrap();
+        let decoded = huffman.decode_owned(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_with_max_length_rejects_too_small_limit() {
+        let weights = vec![(0, 1), (1, 1), (2, 1), (3, 1), (4, 1)];
+        assert!(matches!(
+            Huffman::with_max_length(weights, 2),
+            Err(Error::InvalidWeights(_))
+        ));
+    }
+
+    #[test]
+    fn test_with_max_length_single_symbol() {
+        let weights = vec![(0, 5)];
+        let huffman = Huffman::with_max_length(weights, 3).unwrap();
+        assert_eq!(huffman.code_lengths(), vec![(0, 1)]);
+
+        let data = vec![0, 0, 0];
+        let encoded = huffman.encode(&data).unwrap();
+        let decoded = huffman.decode_owned(&encoded);
+        assert_eq!(data, decoded);
+    }
 }